@@ -0,0 +1,78 @@
+use std::fmt;
+
+use clap::Parser;
+
+/// The release channel a promotion is being run for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Channel {
+    Nightly,
+    Beta,
+    Stable,
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Channel::Nightly => "nightly",
+            Channel::Beta => "beta",
+            Channel::Stable => "stable",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// The `promote-<name>` action to run, one variant per registered [`StandaloneComponent`].
+///
+/// [`StandaloneComponent`]: crate::standalone_component::StandaloneComponent
+#[derive(clap::Subcommand, Debug)]
+pub enum Action {
+    /// Promote a new `rustup` release. See [`crate::rustup::RUSTUP`].
+    #[command(name = "promote-rustup")]
+    PromoteRustup,
+}
+
+/// Configuration for a promotion run, assembled from CLI flags and environment variables.
+#[derive(Parser, Debug)]
+pub struct Config {
+    #[command(subcommand)]
+    pub action: Action,
+
+    /// The release channel to promote.
+    #[arg(long, value_enum)]
+    pub channel: Channel,
+
+    /// Promote this commit instead of the channel branch's current HEAD.
+    #[arg(long)]
+    pub override_commit: Option<String>,
+
+    /// S3 bucket CI uploads build artifacts to.
+    #[arg(long, env = "DOWNLOAD_BUCKET")]
+    pub download_bucket: String,
+
+    /// Key prefix within `download_bucket` that CI copies each commit's build artifacts under.
+    #[arg(long, default_value = "dl")]
+    pub download_dir: String,
+
+    /// S3 bucket promoted artifacts and manifests are uploaded to.
+    #[arg(long, env = "UPLOAD_BUCKET")]
+    pub upload_bucket: String,
+
+    /// Key prefix within `upload_bucket` that promoted artifacts and manifests are rooted at.
+    #[arg(long)]
+    pub upload_dir: String,
+
+    /// The target triple of the platform this promotion is running on, used to pick which
+    /// downloaded artifact gets smoke-tested.
+    #[arg(long)]
+    pub host_target: String,
+
+    /// Allow promoting a version that isn't newer than what's already published, or
+    /// re-archiving a version that was already archived.
+    #[arg(long)]
+    pub allow_rerelease: bool,
+
+    /// Log what would be changed without uploading, promoting, or deleting anything.
+    #[arg(long)]
+    pub dry_run: bool,
+}
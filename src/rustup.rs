@@ -1,220 +1,120 @@
-use std::fs;
-use std::path::{Path, PathBuf};
-
-use anyhow::{anyhow, Error};
-use curl::easy::Easy;
-use serde::Deserialize;
-
-use crate::config::Channel;
-use crate::curl_helper::BodyExt;
-use crate::{run, Context};
-
-impl Context {
-    /// Promote a `rustup` release
-    ///
-    /// The [release process] for `rustup` involves copying existing artifacts from one S3 bucket to
-    /// another, updating the manifest, and archiving the artifacts for long-term storage.
-    ///
-    /// `rustup` uses different branches to manage releases. Whenever a commit is pushed to the
-    /// `stable` branch in [rust-lang/rustup], GitHub Actions workflows build release artifacts and
-    /// copy them into `s3://rustup-builds/builds/${commit-sha}/`.
-    ///
-    /// When a new release is cut and this method is invoked, it downloads the artifacts from that
-    /// bucket (which must always be set as the `DOWNLOAD_BUCKET` variable). A copy of the artifacts
-    /// is archived in `s3://${UPLOAD_BUCKET}/rustup/archive/${version}/`, where `version` is
-    /// derived from the Cargo.toml file in the `stable` branch. `UPLOAD_BUCKET` can either be the
-    /// `dev-static` or the `static` bucket.
-    ///
-    /// If the release is for the `stable` channel, the artifacts are also copied to the `dist/`
-    /// path in the `UPLOAD_BUCKET` bucket. The `dist/` path is used by the `rustup` installer to
-    /// download the latest release.
-    ///
-    /// Then, the `release-stable.toml` manifest is updated with the new version and copied to
-    /// `s3://${UPLOAD_BUCKET}/rustup/release-stable.toml`.
-    ///
-    /// [release process]: https://rust-lang.github.io/rustup/dev-guide/release-process.html
-    /// [rust-lang/rustup]: https://github.com/rust-lang/rustup
-    pub fn promote_rustup(&mut self) -> anyhow::Result<()> {
-        // Rustup only has beta and stable releases, so we fail fast when trying to promote nightly
-        self.enforce_rustup_channel()?;
-
-        // Get the latest commit from the `stable` branch or use the user-provided override
-        let head_sha = self.get_commit_sha_for_rustup_release()?;
-
-        // The commit on the `stable` branch is used to determine the version number
-        let version = self.get_next_rustup_version(&head_sha)?;
-
-        // Download the Rustup artifacts from S3
-        let dist_dir = self.download_rustup_artifacts(&head_sha)?;
-
-        // Archive the artifacts
-        self.archive_rustup_artifacts(&dist_dir, &version)?;
-
-        if self.config.channel == Channel::Stable {
-            // Promote the artifacts to the release bucket
-            self.promote_rustup_artifacts(&dist_dir)?;
-        }
-
-        // Update the release number
-        self.update_rustup_release(&version)?;
-
-        Ok(())
-    }
-
-    fn enforce_rustup_channel(&self) -> anyhow::Result<()> {
-        println!("Checking channel...");
-
-        if self.config.channel != Channel::Stable && self.config.channel != Channel::Beta {
-            return Err(anyhow!(
-                "promoting rustup is only supported for the stable and beta channels"
-            ));
-        }
-
-        Ok(())
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::anyhow;
+
+use crate::config::{Action, Channel};
+use crate::standalone_component::{StandaloneComponent, VersionSource};
+use crate::Context;
+
+/// The `rustup` [`StandaloneComponent`], promoted through `promote-rustup`.
+///
+/// The [release process] for `rustup` involves copying existing artifacts from one S3 bucket to
+/// another, updating the manifest, and archiving the artifacts for long-term storage.
+///
+/// `rustup` uses different branches to manage releases. Whenever a commit is pushed to the
+/// `stable` branch in [rust-lang/rustup], GitHub Actions workflows build release artifacts and
+/// copy them into `s3://rustup-builds/builds/${commit-sha}/`.
+///
+/// When a new release is cut, [`Context::promote_standalone_component`] downloads the artifacts
+/// from that bucket (which must always be set as the `DOWNLOAD_BUCKET` variable). A copy of the
+/// artifacts is archived in `s3://${UPLOAD_BUCKET}/rustup/archive/${version}/`, where `version` is
+/// derived from the Cargo.toml file in the `stable` branch. `UPLOAD_BUCKET` can either be the
+/// `dev-static` or the `static` bucket.
+///
+/// If the release is for the `stable` channel, the archived objects are also copied to the
+/// `dist/` path in the `UPLOAD_BUCKET` bucket, using a server-side S3 `CopyObject` rather than
+/// uploading the artifacts from disk a second time. The `dist/` path is used by the `rustup`
+/// installer to download the latest release.
+///
+/// After archiving and after promoting, any object under that prefix that doesn't correspond to
+/// an artifact from this build is deleted, so `archive/${version}/` and `dist/` never end up
+/// serving binaries for targets the build dropped, or leftovers from a previously failed
+/// promotion. Pass `--dry-run` to only log what would be uploaded, copied, or removed, without
+/// changing anything in `UPLOAD_BUCKET`.
+///
+/// Before archiving, a SHA256 checksum is computed for every downloaded artifact and written next
+/// to it as a `<file>.sha256` sidecar, so both the archived copy and the `dist/` copy carry
+/// checksums that `rustup`'s self-updater can use to detect tampered or truncated downloads. The
+/// same checksums are recorded in the `[checksums]` table of the `release-stable.toml` manifest.
+///
+/// The downloaded build is also smoke-tested before anything is archived or promoted: the
+/// artifact matching the host running this promotion is run with `--version`, and its output is
+/// checked against the `version` derived from `Cargo.toml`. A broken or mismatched build fails
+/// the promotion instead of being published.
+///
+/// Before any of that, the `version` is checked against what is already published: if it is not
+/// strictly newer than the `version` in the live `release-stable.toml`, or if
+/// `archive/${version}/` already exists in `UPLOAD_BUCKET`, the promotion is refused unless
+/// `--allow-rerelease` is passed. This is the same "is this bump actually greater" check
+/// `rustup`'s self-updater relies on to decide whether to upgrade.
+///
+/// [release process]: https://rust-lang.github.io/rustup/dev-guide/release-process.html
+/// [rust-lang/rustup]: https://github.com/rust-lang/rustup
+pub const RUSTUP: StandaloneComponent = StandaloneComponent {
+    name: "rustup",
+    repo: "rust-lang/rustup",
+    branch_for_channel: rustup_branch_for_channel,
+    version_source: VersionSource::CargoToml("Cargo.toml"),
+    download_prefix: rustup_download_prefix,
+    archive_prefix: rustup_archive_prefix,
+    dist_prefix: "dist/",
+    manifest_name: "release-stable.toml",
+    extra_smoke_test: Some(dry_run_rustup_init),
+};
+
+fn rustup_branch_for_channel(channel: Channel) -> anyhow::Result<String> {
+    // Rustup only has beta and stable releases, so we fail fast when trying to promote nightly
+    if channel != Channel::Stable && channel != Channel::Beta {
+        return Err(anyhow::anyhow!(
+            "promoting rustup is only supported for the stable and beta channels"
+        ));
     }
 
-    fn get_commit_sha_for_rustup_release(&self) -> anyhow::Result<String> {
-        match &self.config.override_commit {
-            Some(sha) => Ok(sha.clone()),
-            None => self.get_head_sha_for_rustup(),
-        }
-    }
-
-    fn get_head_sha_for_rustup(&self) -> anyhow::Result<String> {
-        #[derive(Deserialize)]
-        struct Commit {
-            sha: String,
-        }
-
-        let url = format!(
-            "https://api.github.com/repos/rust-lang/rustup/commits/{}",
-            self.config.channel
-        );
+    Ok(channel.to_string())
+}
 
-        let mut client = Easy::new();
-        client.url(&url)?;
-        client.useragent("rust-lang/promote-release")?;
+fn rustup_download_prefix(download_dir: &str, sha: &str) -> String {
+    format!("{download_dir}/{sha}")
+}
 
-        let commit: Commit = client.without_body().send_with_response()?;
+fn rustup_archive_prefix(version: &str) -> String {
+    format!("archive/{version}/")
+}
 
-        Ok(commit.sha)
+/// Install `rustup-init` into a throwaway `CARGO_HOME`/`RUSTUP_HOME` to catch install-time
+/// regressions that `--version` alone wouldn't surface.
+fn dry_run_rustup_init(binary: &Path, sandbox: &Path) -> anyhow::Result<()> {
+    let status = Command::new(binary)
+        .arg("-y")
+        .arg("--no-modify-path")
+        .arg("--default-toolchain")
+        .arg("none")
+        .env("CARGO_HOME", sandbox.join("cargo-home"))
+        .env("RUSTUP_HOME", sandbox.join("rustup-home"))
+        .status()?;
+
+    if !status.success() {
+        return Err(anyhow!(
+            "dry-run install of {} failed with {}",
+            binary.display(),
+            status
+        ));
     }
 
-    fn get_next_rustup_version(&self, sha: &str) -> anyhow::Result<String> {
-        println!("Getting next Rustup version from Cargo.toml...");
-
-        #[derive(Deserialize)]
-        struct Content {
-            content: String,
-        }
-
-        #[derive(Deserialize)]
-        struct CargoToml {
-            package: Package,
-        }
+    Ok(())
+}
 
-        #[derive(Deserialize)]
-        struct Package {
-            version: String,
+impl Context {
+    /// Run the `promote-<name>` [`Action`] selected on the command line.
+    pub async fn run(&mut self, action: &Action) -> anyhow::Result<()> {
+        match action {
+            Action::PromoteRustup => self.promote_rustup().await,
         }
-
-        let url =
-            format!("https://api.github.com/repos/rust-lang/rustup/contents/Cargo.toml?ref={sha}");
-
-        let mut client = Easy::new();
-        client.url(&url)?;
-        client.useragent("rust-lang/promote-release")?;
-
-        let content: Content = client.without_body().send_with_response()?;
-        let decoded_content = base64::decode(content.content.replace('\n', ""))?;
-        let cargo_toml = String::from_utf8(decoded_content)?;
-
-        let toml: CargoToml = toml::from_str(&cargo_toml)?;
-
-        Ok(toml.package.version)
-    }
-
-    fn download_rustup_artifacts(&mut self, sha: &str) -> Result<PathBuf, Error> {
-        println!("Downloading artifacts from dev-static...");
-
-        let dl = self.dl_dir().join("dist");
-        // Remove the directory if it exists, otherwise just ignore.
-        let _ = fs::remove_dir_all(&dl);
-        fs::create_dir_all(&dl)?;
-
-        let download_path = format!("{}/{}", self.config.download_dir, sha);
-
-        run(self
-            .aws_s3()
-            .arg("cp")
-            .arg("--recursive")
-            .arg("--only-show-errors")
-            .arg(self.s3_artifacts_url(&download_path))
-            .arg(format!("{}/", dl.display())))?;
-
-        Ok(dl)
     }
 
-    fn archive_rustup_artifacts(&mut self, dist_dir: &Path, version: &str) -> Result<(), Error> {
-        println!("Archiving artifacts for version {version}...");
-
-        let path = format!("archive/{}/", version);
-
-        self.upload_rustup_artifacts(dist_dir, &path)
-    }
-
-    fn promote_rustup_artifacts(&mut self, dist_dir: &Path) -> Result<(), Error> {
-        println!("Promoting artifacts to dist/...");
-
-        let release_bucket_url = format!(
-            "s3://{}/{}/dist/",
-            self.config.upload_bucket, self.config.upload_dir,
-        );
-
-        run(self
-            .aws_s3()
-            .arg("cp")
-            .arg("--recursive")
-            .arg("--only-show-errors")
-            .arg(format!("{}/", dist_dir.display()))
-            .arg(&release_bucket_url))
-    }
-
-    fn upload_rustup_artifacts(&mut self, dist_dir: &Path, target_path: &str) -> Result<(), Error> {
-        run(self
-            .aws_s3()
-            .arg("cp")
-            .arg("--recursive")
-            .arg("--only-show-errors")
-            .arg(format!("{}/", dist_dir.display()))
-            .arg(format!(
-                "s3://{}/{}/{}",
-                self.config.upload_bucket, self.config.upload_dir, target_path
-            )))
-    }
-
-    fn update_rustup_release(&mut self, version: &str) -> Result<(), Error> {
-        println!("Updating version and manifest...");
-
-        let manifest_path = self.dl_dir().join("release-stable.toml");
-        let manifest = format!(
-            r#"
-schema-version = '1'
-version = '{}'
-            "#,
-            version
-        );
-
-        fs::write(&manifest_path, manifest)?;
-
-        run(self
-            .aws_s3()
-            .arg("cp")
-            .arg("--only-show-errors")
-            .arg(manifest_path)
-            .arg(format!(
-                "s3://{}/{}/release-stable.toml",
-                self.config.upload_bucket, self.config.upload_dir
-            )))
+    /// Promote a `rustup` release. See [`RUSTUP`] for what that involves.
+    pub async fn promote_rustup(&mut self) -> anyhow::Result<()> {
+        self.promote_standalone_component(&RUSTUP).await
     }
 }
@@ -0,0 +1,399 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::operation::get_object::GetObjectError;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{Delete, ObjectIdentifier};
+use aws_sdk_s3::Client;
+use futures::stream::{self, TryStreamExt};
+use tokio::sync::OnceCell;
+
+use crate::Context;
+
+/// Batch size for a single `DeleteObjects` request; this is an S3-enforced limit, not a tunable.
+const DELETE_OBJECTS_BATCH_SIZE: usize = 1000;
+
+/// How many requests a recursive transfer (`download_prefix`/`upload_prefix`/`copy_prefix`) keeps
+/// in flight at once, so a release with dozens of per-target artifacts doesn't serialize into one
+/// request at a time, while still bounding how many connections/file handles are open at once.
+const MAX_CONCURRENT_REQUESTS: usize = 16;
+
+/// How many attempts a single request gets in total (the first try plus up to three retries after
+/// transient failures), and the base delay an attempt's exponential backoff starts from.
+const MAX_ATTEMPTS: u32 = 4;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+/// The process-wide client, built once on first use rather than on every [`Context::s3`] call, so
+/// repeated calls don't each re-run the credential-provider chain (including a potential IMDS
+/// round trip on EC2). `Context` itself lives outside this module, so this lives in a static
+/// rather than a field on it; `Client` is a cheap `Arc`-backed handle to clone out of it.
+static CLIENT: OnceCell<Client> = OnceCell::const_new();
+
+/// Thin wrapper around [`aws_sdk_s3::Client`] exposing just the handful of operations the
+/// promotion pipeline needs, so callers deal in buckets, prefixes, and local paths instead of the
+/// SDK's request builders.
+pub struct S3 {
+    client: Client,
+}
+
+impl S3 {
+    async fn new() -> Self {
+        let client = CLIENT
+            .get_or_init(|| async {
+                let config = aws_config::load_from_env().await;
+                Client::new(&config)
+            })
+            .await
+            .clone();
+
+        Self { client }
+    }
+
+    /// List every object key under `prefix` in `bucket`.
+    pub async fn list_keys(&self, bucket: &str, prefix: &str) -> anyhow::Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+
+        loop {
+            let token = continuation_token.clone();
+            let response = retry(|_| true, || {
+                let mut request = self.client.list_objects_v2().bucket(bucket).prefix(prefix);
+                if let Some(token) = &token {
+                    request = request.continuation_token(token);
+                }
+                request.send()
+            })
+            .await
+            .map_err(|err| anyhow!("failed to list s3://{bucket}/{prefix}: {err}"))?;
+
+            keys.extend(
+                response
+                    .contents()
+                    .iter()
+                    .filter_map(|object| object.key().map(String::from)),
+            );
+
+            continuation_token = response.next_continuation_token().map(String::from);
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(keys)
+    }
+
+    /// Whether any object exists under `prefix` in `bucket`.
+    pub async fn prefix_exists(&self, bucket: &str, prefix: &str) -> anyhow::Result<bool> {
+        Ok(!self.list_keys(bucket, prefix).await?.is_empty())
+    }
+
+    /// Fetch the contents of `bucket`/`key`, or `None` if the object doesn't exist.
+    pub async fn get_object(&self, bucket: &str, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let response = retry(
+            |err: &SdkError<GetObjectError>| {
+                !matches!(err, SdkError::ServiceError(e) if matches!(e.err(), GetObjectError::NoSuchKey(_)))
+            },
+            || self.client.get_object().bucket(bucket).key(key).send(),
+        )
+        .await;
+
+        let object = match response {
+            Ok(object) => object,
+            Err(SdkError::ServiceError(err)) if matches!(err.err(), GetObjectError::NoSuchKey(_)) => {
+                return Ok(None)
+            }
+            Err(err) => return Err(anyhow!("failed to fetch s3://{bucket}/{key}: {err}")),
+        };
+
+        let body = object
+            .body
+            .collect()
+            .await
+            .map_err(|err| anyhow!("failed to read s3://{bucket}/{key}: {err}"))?;
+
+        Ok(Some(body.into_bytes().to_vec()))
+    }
+
+    /// Upload the contents of the local file at `path` to `bucket`/`key`.
+    pub async fn put_object(&self, path: &Path, bucket: &str, key: &str) -> anyhow::Result<()> {
+        retry(|_: &anyhow::Error| true, || async {
+            // Rebuilt fresh on every attempt: a `ByteStream` is single-use, so a retry can't
+            // reuse the body from a previous attempt.
+            let body = ByteStream::from_path(path)
+                .await
+                .map_err(|err| anyhow!("failed to read {}: {err}", path.display()))?;
+
+            self.client
+                .put_object()
+                .bucket(bucket)
+                .key(key)
+                .body(body)
+                .send()
+                .await
+                .map_err(|err| {
+                    anyhow!("failed to upload {} to s3://{bucket}/{key}: {err}", path.display())
+                })?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Download every object under `prefix` in `bucket` into `destination`, mirroring each key's
+    /// path (relative to `prefix`) as a path relative to `destination`. Up to
+    /// `MAX_CONCURRENT_REQUESTS` objects are downloaded at once.
+    pub async fn download_prefix(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        destination: &Path,
+    ) -> anyhow::Result<()> {
+        let prefix_with_slash = format!("{}/", prefix.trim_end_matches('/'));
+        let keys = self.list_keys(bucket, prefix).await?;
+
+        stream::iter(keys.into_iter().map(Ok::<_, anyhow::Error>))
+            .try_for_each_concurrent(MAX_CONCURRENT_REQUESTS, |key| {
+                let prefix_with_slash = &prefix_with_slash;
+                async move {
+                    let relative = key.strip_prefix(prefix_with_slash.as_str()).unwrap_or(&key);
+                    let contents = self
+                        .get_object(bucket, &key)
+                        .await?
+                        .ok_or_else(|| anyhow!("s3://{bucket}/{key} disappeared mid-download"))?;
+
+                    let path = destination.join(relative);
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(&path, contents)?;
+
+                    Ok(())
+                }
+            })
+            .await
+    }
+
+    /// Upload every file under `source` to `bucket`, keyed under `prefix` by each file's path
+    /// relative to `source`. Up to `MAX_CONCURRENT_REQUESTS` uploads run at once.
+    pub async fn upload_prefix(&self, source: &Path, bucket: &str, prefix: &str) -> anyhow::Result<()> {
+        let prefix = prefix.trim_end_matches('/');
+        let paths = walk_files(source)?;
+
+        stream::iter(paths.into_iter().map(Ok::<_, anyhow::Error>))
+            .try_for_each_concurrent(MAX_CONCURRENT_REQUESTS, |path| async move {
+                let relative = path
+                    .strip_prefix(source)?
+                    .to_str()
+                    .ok_or_else(|| anyhow!("path {} is not valid UTF-8", path.display()))?
+                    .replace(std::path::MAIN_SEPARATOR, "/");
+
+                self.put_object(&path, bucket, &format!("{prefix}/{relative}"))
+                    .await
+            })
+            .await
+    }
+
+    /// Copy every object under `source_prefix` in `source_bucket` to the equivalent key under
+    /// `destination_prefix` in `destination_bucket`, using a server-side `CopyObject` rather than
+    /// round-tripping the bytes through this process. Up to `MAX_CONCURRENT_REQUESTS` copies run
+    /// at once.
+    pub async fn copy_prefix(
+        &self,
+        source_bucket: &str,
+        source_prefix: &str,
+        destination_bucket: &str,
+        destination_prefix: &str,
+    ) -> anyhow::Result<()> {
+        let source_prefix_with_slash = format!("{}/", source_prefix.trim_end_matches('/'));
+        let destination_prefix = destination_prefix.trim_end_matches('/');
+        let keys = self.list_keys(source_bucket, source_prefix).await?;
+
+        stream::iter(keys.into_iter().map(Ok::<_, anyhow::Error>))
+            .try_for_each_concurrent(MAX_CONCURRENT_REQUESTS, |key| {
+                let source_prefix_with_slash = &source_prefix_with_slash;
+                async move {
+                    let relative = key.strip_prefix(source_prefix_with_slash.as_str()).unwrap_or(&key);
+                    self.copy_object(
+                        source_bucket,
+                        &key,
+                        destination_bucket,
+                        &format!("{destination_prefix}/{relative}"),
+                    )
+                    .await
+                }
+            })
+            .await
+    }
+
+    /// Copy a single object server-side from `source_bucket`/`source_key` to
+    /// `destination_bucket`/`destination_key`.
+    pub async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        destination_bucket: &str,
+        destination_key: &str,
+    ) -> anyhow::Result<()> {
+        retry(|_| true, || {
+            self.client
+                .copy_object()
+                .copy_source(format!("{source_bucket}/{source_key}"))
+                .bucket(destination_bucket)
+                .key(destination_key)
+                .send()
+        })
+        .await
+        .map_err(|err| {
+            anyhow!(
+                "failed to copy s3://{source_bucket}/{source_key} to \
+                 s3://{destination_bucket}/{destination_key}: {err}"
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// Delete every key in `keys` from `bucket`, batching requests to stay under S3's
+    /// per-request object limit.
+    pub async fn delete_objects(&self, bucket: &str, keys: &[String]) -> anyhow::Result<()> {
+        for batch in keys.chunks(DELETE_OBJECTS_BATCH_SIZE) {
+            let objects = batch
+                .iter()
+                .map(|key| ObjectIdentifier::builder().key(key).build())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| anyhow!("failed to build delete request: {err}"))?;
+
+            let delete = Delete::builder()
+                .set_objects(Some(objects))
+                .build()
+                .map_err(|err| anyhow!("failed to build delete request: {err}"))?;
+
+            retry(|_| true, || {
+                self.client
+                    .delete_objects()
+                    .bucket(bucket)
+                    .delete(delete.clone())
+                    .send()
+            })
+            .await
+            .map_err(|err| anyhow!("failed to delete objects from s3://{bucket}: {err}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Retry `operation` with exponential backoff while `is_retryable` returns `true` for the error it
+/// returned, instead of silently relying on the SDK's default retry config. Transient failures
+/// (throttling, timeouts, connection resets) usually clear up on their own within a few attempts;
+/// errors `is_retryable` rejects (e.g. a missing key) are returned immediately.
+async fn retry<F, Fut, T, E>(is_retryable: impl Fn(&E) -> bool, mut operation: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt + 1 < MAX_ATTEMPTS && is_retryable(&err) => {
+                attempt += 1;
+                tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Recursively collect the paths of every file under `dir`.
+fn walk_files(dir: &Path) -> anyhow::Result<Vec<std::path::PathBuf>> {
+    let mut paths = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let path = entry?.path();
+
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                paths.push(path);
+            }
+        }
+    }
+
+    Ok(paths)
+}
+
+impl Context {
+    /// A client for the handful of S3 operations the promotion pipeline needs. Backed by a client
+    /// built once per process and cloned out of a cache, not rebuilt on every call.
+    pub async fn s3(&self) -> S3 {
+        S3::new().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walk_files_finds_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("top"), b"").unwrap();
+        fs::write(dir.path().join("a/b/nested"), b"").unwrap();
+
+        let mut found: Vec<String> = walk_files(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|path| {
+                path.strip_prefix(dir.path())
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .replace(std::path::MAIN_SEPARATOR, "/")
+            })
+            .collect();
+        found.sort();
+
+        assert_eq!(found, vec!["a/b/nested", "top"]);
+    }
+
+    #[tokio::test]
+    async fn retry_stops_as_soon_as_the_operation_succeeds() {
+        let mut attempts = 0;
+
+        let result = retry(|_: &&str| true, || {
+            attempts += 1;
+            async move {
+                if attempts < 2 {
+                    Err("transient")
+                } else {
+                    Ok(attempts)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(2));
+    }
+
+    #[tokio::test]
+    async fn retry_gives_up_immediately_on_a_non_retryable_error() {
+        let mut attempts = 0;
+
+        let result = retry(|_: &&str| false, || {
+            attempts += 1;
+            async move { Err::<(), _>("not found") }
+        })
+        .await;
+
+        assert_eq!(result, Err("not found"));
+        assert_eq!(attempts, 1);
+    }
+}
@@ -0,0 +1,819 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::anyhow;
+use curl::easy::Easy;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tempfile::tempdir;
+
+use crate::config::Channel;
+use crate::curl_helper::BodyExt;
+use crate::Context;
+
+/// Where a standalone component's version number is read from in its own repo.
+pub enum VersionSource {
+    /// The `package.version` field of the `Cargo.toml` at this path.
+    CargoToml(&'static str),
+    /// A plain-text file containing just the version number, at this path (e.g. `src/version`).
+    SrcVersion(&'static str),
+}
+
+/// Describes how to promote one independently-released component the same way `rustup` has
+/// always been promoted: download artifacts built for a commit on a channel's branch from
+/// `DOWNLOAD_BUCKET`, archive them, optionally promote them to `dist/`, and update a manifest.
+///
+/// Register a `StandaloneComponent` instead of copying [`Context::promote_standalone_component`]'s
+/// pipeline for every new tool that is released the same way `rustup` is. See [`crate::rustup`]
+/// for the `rustup` instance and how it's wired through `promote-rustup`.
+pub struct StandaloneComponent {
+    /// Name used to label log output (and, by convention, the `promote-<name>` action).
+    pub name: &'static str,
+    /// The `owner/repo` slug this component is released from.
+    pub repo: &'static str,
+    /// Map a release channel to the branch GitHub Actions builds artifacts from, or fail if the
+    /// component isn't released on that channel.
+    pub branch_for_channel: fn(Channel) -> anyhow::Result<String>,
+    /// Where the version number comes from in the component's repo.
+    pub version_source: VersionSource,
+    /// `DOWNLOAD_BUCKET` key prefix the artifacts built for a commit are copied to by CI.
+    pub download_prefix: fn(download_dir: &str, sha: &str) -> String,
+    /// `UPLOAD_BUCKET` key prefix artifacts are archived under for a given version.
+    pub archive_prefix: fn(version: &str) -> String,
+    /// `UPLOAD_BUCKET` key prefix the stable channel's artifacts are promoted to.
+    pub dist_prefix: &'static str,
+    /// Manifest file name uploaded alongside the artifacts (e.g. `release-stable.toml`).
+    pub manifest_name: &'static str,
+    /// Extra smoke-testing beyond the `--version` check every component gets, run against the
+    /// copy of the host target's binary in `sandbox` after it's been made executable. `None` if
+    /// `--version` is all this component needs.
+    pub extra_smoke_test: Option<fn(binary: &Path, sandbox: &Path) -> anyhow::Result<()>>,
+}
+
+#[derive(Serialize)]
+struct Manifest<'a> {
+    #[serde(rename = "schema-version")]
+    schema_version: &'a str,
+    version: &'a str,
+    checksums: &'a BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct PublishedManifest {
+    version: String,
+}
+
+impl Context {
+    /// Run the generic standalone-component release pipeline for `component`.
+    pub async fn promote_standalone_component(
+        &mut self,
+        component: &StandaloneComponent,
+    ) -> anyhow::Result<()> {
+        let branch = self.enforce_component_channel(component)?;
+
+        // Get the latest commit from the channel's branch or use the user-provided override
+        let head_sha = self.commit_sha_for_component(component, &branch)?;
+
+        // The commit on the branch is used to determine the version number
+        let version = self.component_version(component, &head_sha)?;
+
+        // Refuse to re-publish a version that isn't newer than what's already live
+        self.enforce_component_version_is_new(component, &version)
+            .await?;
+
+        // Download the component's artifacts from S3
+        let dist_dir = self
+            .download_component_artifacts(component, &head_sha)
+            .await?;
+
+        // Compute and write the checksums that will be recorded in the manifest and shipped
+        // alongside each artifact
+        let checksums = self.generate_component_checksums(&dist_dir)?;
+
+        // Make sure the build actually works before we archive or promote it
+        self.smoke_test_component_artifacts(component, &dist_dir, &version)?;
+
+        // Archive the artifacts
+        self.archive_component_artifacts(component, &dist_dir, &version)
+            .await?;
+
+        if self.config.channel == Channel::Stable {
+            // Promote the archived artifacts to the release bucket
+            self.promote_component_artifacts(component, &dist_dir, &version)
+                .await?;
+        }
+
+        // Update the release manifest
+        self.update_component_release(component, &version, &checksums)
+            .await?;
+
+        Ok(())
+    }
+
+    fn enforce_component_channel(&self, component: &StandaloneComponent) -> anyhow::Result<String> {
+        println!("Checking channel...");
+
+        (component.branch_for_channel)(self.config.channel)
+    }
+
+    fn commit_sha_for_component(
+        &self,
+        component: &StandaloneComponent,
+        branch: &str,
+    ) -> anyhow::Result<String> {
+        match &self.config.override_commit {
+            Some(sha) => Ok(sha.clone()),
+            None => self.head_sha_for_branch(component, branch),
+        }
+    }
+
+    fn head_sha_for_branch(
+        &self,
+        component: &StandaloneComponent,
+        branch: &str,
+    ) -> anyhow::Result<String> {
+        #[derive(Deserialize)]
+        struct Commit {
+            sha: String,
+        }
+
+        let url = format!(
+            "https://api.github.com/repos/{}/commits/{branch}",
+            component.repo
+        );
+
+        let mut client = Easy::new();
+        client.url(&url)?;
+        client.useragent("rust-lang/promote-release")?;
+
+        let commit: Commit = client.without_body().send_with_response()?;
+
+        Ok(commit.sha)
+    }
+
+    fn component_version(
+        &self,
+        component: &StandaloneComponent,
+        sha: &str,
+    ) -> anyhow::Result<String> {
+        println!("Getting next {} version...", component.name);
+
+        match component.version_source {
+            VersionSource::CargoToml(path) => self.version_from_cargo_toml(component, sha, path),
+            VersionSource::SrcVersion(path) => self.version_from_src_version(component, sha, path),
+        }
+    }
+
+    fn version_from_cargo_toml(
+        &self,
+        component: &StandaloneComponent,
+        sha: &str,
+        path: &str,
+    ) -> anyhow::Result<String> {
+        #[derive(Deserialize)]
+        struct Content {
+            content: String,
+        }
+
+        #[derive(Deserialize)]
+        struct CargoToml {
+            package: Package,
+        }
+
+        #[derive(Deserialize)]
+        struct Package {
+            version: String,
+        }
+
+        let url = format!(
+            "https://api.github.com/repos/{}/contents/{path}?ref={sha}",
+            component.repo
+        );
+
+        let mut client = Easy::new();
+        client.url(&url)?;
+        client.useragent("rust-lang/promote-release")?;
+
+        let content: Content = client.without_body().send_with_response()?;
+        let decoded_content = base64::decode(content.content.replace('\n', ""))?;
+        let cargo_toml = String::from_utf8(decoded_content)?;
+
+        let toml: CargoToml = toml::from_str(&cargo_toml)?;
+
+        Ok(toml.package.version)
+    }
+
+    fn version_from_src_version(
+        &self,
+        component: &StandaloneComponent,
+        sha: &str,
+        path: &str,
+    ) -> anyhow::Result<String> {
+        #[derive(Deserialize)]
+        struct Content {
+            content: String,
+        }
+
+        let url = format!(
+            "https://api.github.com/repos/{}/contents/{path}?ref={sha}",
+            component.repo
+        );
+
+        let mut client = Easy::new();
+        client.url(&url)?;
+        client.useragent("rust-lang/promote-release")?;
+
+        let content: Content = client.without_body().send_with_response()?;
+        let decoded_content = base64::decode(content.content.replace('\n', ""))?;
+
+        Ok(String::from_utf8(decoded_content)?.trim().to_owned())
+    }
+
+    /// Refuse to promote `version` unless it is strictly newer than whatever is currently
+    /// published and hasn't already been archived, so we can't silently downgrade or clobber a
+    /// previous release. `--allow-rerelease` bypasses both checks.
+    async fn enforce_component_version_is_new(
+        &mut self,
+        component: &StandaloneComponent,
+        version: &str,
+    ) -> anyhow::Result<()> {
+        println!("Checking that {version} is newer than the published release...");
+
+        let incoming = Version::parse(version)?;
+        let published = self.published_component_version(component).await?;
+        let published_version = published.as_deref().map(Version::parse).transpose()?;
+
+        let may_be_promoted = Self::version_may_be_promoted(
+            &incoming,
+            published_version.as_ref(),
+            self.config.allow_rerelease,
+        );
+
+        if !may_be_promoted {
+            return Err(anyhow!(
+                "refusing to promote {} {version}: {} is already published \
+                 (pass --allow-rerelease to override)",
+                component.name,
+                published.unwrap()
+            ));
+        }
+
+        let archive_prefix = format!(
+            "{}/{}",
+            self.config.upload_dir,
+            (component.archive_prefix)(version)
+        );
+
+        if !self.config.allow_rerelease
+            && self
+                .s3()
+                .await
+                .prefix_exists(&self.config.upload_bucket, &archive_prefix)
+                .await?
+        {
+            return Err(anyhow!(
+                "refusing to promote {} {version}: {archive_prefix} already exists in {} \
+                 (pass --allow-rerelease to override)",
+                component.name,
+                self.config.upload_bucket
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Whether `incoming` may be promoted given the currently `published` version (if any),
+    /// honoring `--allow-rerelease`. Pulled out of [`Self::enforce_component_version_is_new`] so
+    /// the comparison itself is unit-testable without touching S3.
+    fn version_may_be_promoted(
+        incoming: &Version,
+        published: Option<&Version>,
+        allow_rerelease: bool,
+    ) -> bool {
+        match published {
+            Some(published) => allow_rerelease || incoming > published,
+            None => true,
+        }
+    }
+
+    /// Fetch and parse the `version` from the currently published manifest, or `None` if no
+    /// release has been published to `UPLOAD_BUCKET` yet.
+    async fn published_component_version(
+        &mut self,
+        component: &StandaloneComponent,
+    ) -> anyhow::Result<Option<String>> {
+        let key = format!("{}/{}", self.config.upload_dir, component.manifest_name);
+
+        let contents = match self
+            .s3()
+            .await
+            .get_object(&self.config.upload_bucket, &key)
+            .await?
+        {
+            Some(contents) => contents,
+            None => return Ok(None),
+        };
+
+        let manifest: PublishedManifest = toml::from_str(&String::from_utf8(contents)?)?;
+
+        Ok(Some(manifest.version))
+    }
+
+    async fn download_component_artifacts(
+        &mut self,
+        component: &StandaloneComponent,
+        sha: &str,
+    ) -> anyhow::Result<PathBuf> {
+        println!("Downloading {} artifacts from dev-static...", component.name);
+
+        let dl = self.dl_dir().join("dist");
+        // Remove the directory if it exists, otherwise just ignore.
+        let _ = fs::remove_dir_all(&dl);
+        fs::create_dir_all(&dl)?;
+
+        let download_path = (component.download_prefix)(&self.config.download_dir, sha);
+
+        self.s3().await
+            .download_prefix(&self.config.download_bucket, &download_path, &dl)
+            .await?;
+
+        Ok(dl)
+    }
+
+    /// Compute the SHA256 checksum of every artifact in `dist_dir` and write it next to the
+    /// artifact as a `<file>.sha256` sidecar, so it gets swept up by the subsequent recursive
+    /// uploads the same way the stage0 tooling's `checksums_sha256` sidecars do.
+    ///
+    /// Returns the checksums keyed by the artifact's path relative to `dist_dir`, sorted
+    /// deterministically so the manifest built from them is byte-identical across runs with the
+    /// same inputs.
+    fn generate_component_checksums(
+        &self,
+        dist_dir: &Path,
+    ) -> anyhow::Result<BTreeMap<String, String>> {
+        println!("Generating checksums for artifacts...");
+
+        let mut checksums = BTreeMap::new();
+
+        for path in Self::component_artifact_paths(dist_dir)? {
+            let relative_path = path
+                .strip_prefix(dist_dir)?
+                .to_str()
+                .ok_or_else(|| anyhow!("artifact path {} is not valid UTF-8", path.display()))?
+                .replace(std::path::MAIN_SEPARATOR, "/");
+
+            let mut hasher = Sha256::new();
+            hasher.update(fs::read(&path)?);
+            let checksum = hex::encode(hasher.finalize());
+
+            fs::write(Self::checksum_sidecar_path(&path), format!("{checksum}\n"))?;
+
+            checksums.insert(relative_path, checksum);
+        }
+
+        Ok(checksums)
+    }
+
+    /// The sidecar path for an artifact's checksum: the full file name with `.sha256` appended,
+    /// not `with_extension` (which would replace `rustup-init.tar.gz`'s `.gz` instead of
+    /// appending, and collide with a neighboring `rustup-init.tar.xz`).
+    fn checksum_sidecar_path(artifact: &Path) -> PathBuf {
+        let mut file_name = artifact.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".sha256");
+        artifact.with_file_name(file_name)
+    }
+
+    /// Recursively collect the paths of every file under `dir`, sorted for determinism.
+    fn component_artifact_paths(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+
+        while let Some(current) = pending.pop() {
+            for entry in fs::read_dir(&current)? {
+                let path = entry?.path();
+
+                if path.is_dir() {
+                    pending.push(path);
+                } else {
+                    paths.push(path);
+                }
+            }
+        }
+
+        paths.sort();
+
+        Ok(paths)
+    }
+
+    /// Run the artifact built for this promotion's host target and make sure it reports the
+    /// expected version, failing the promotion if the binary is missing, isn't executable,
+    /// crashes, or reports a different version than the one just derived. Then run
+    /// `component.extra_smoke_test`, if it has one, against that same binary.
+    fn smoke_test_component_artifacts(
+        &self,
+        component: &StandaloneComponent,
+        dist_dir: &Path,
+        version: &str,
+    ) -> anyhow::Result<()> {
+        println!(
+            "Smoke-testing artifacts for {}...",
+            self.config.host_target
+        );
+
+        let artifact = Self::find_component_artifact(dist_dir, &self.config.host_target)?;
+
+        let sandbox = tempdir()?;
+        let file_name = artifact
+            .file_name()
+            .ok_or_else(|| anyhow!("artifact {} has no file name", artifact.display()))?;
+        let binary = sandbox.path().join(file_name);
+        fs::copy(&artifact, &binary)?;
+        Self::mark_executable(&binary)?;
+
+        let reported_version = Self::binary_version(&binary)?;
+        if reported_version != version {
+            return Err(anyhow!(
+                "smoke test failed: {} reports version '{reported_version}', expected '{version}'",
+                binary.display()
+            ));
+        }
+
+        if let Some(extra_smoke_test) = component.extra_smoke_test {
+            extra_smoke_test(&binary, sandbox.path())?;
+        }
+
+        Ok(())
+    }
+
+    /// Find the artifact built for `host_target` among the downloaded artifacts.
+    ///
+    /// The target triple lives in the artifact's directory (`builds/<sha>/<target-triple>/
+    /// <binary>`), not necessarily in its own file name, so this matches against the whole path
+    /// relative to `dist_dir` rather than just the leaf file name.
+    fn find_component_artifact(dist_dir: &Path, host_target: &str) -> anyhow::Result<PathBuf> {
+        Self::component_artifact_paths(dist_dir)?
+            .into_iter()
+            .find(|path| {
+                path.strip_prefix(dist_dir)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .contains(host_target)
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "no artifact for host target '{host_target}' found in {}",
+                    dist_dir.display()
+                )
+            })
+    }
+
+    /// Run `binary --version` and extract the version number from its output.
+    fn binary_version(binary: &Path) -> anyhow::Result<String> {
+        let output = Command::new(binary).arg("--version").output()?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "`{} --version` exited with {}",
+                binary.display(),
+                output.status
+            ));
+        }
+
+        let stdout = String::from_utf8(output.stdout)?;
+
+        stdout
+            .split_whitespace()
+            .nth(1)
+            .map(str::to_owned)
+            .ok_or_else(|| anyhow!("could not parse a version from `{}`", stdout.trim()))
+    }
+
+    #[cfg(unix)]
+    fn mark_executable(path: &Path) -> anyhow::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut permissions = fs::metadata(path)?.permissions();
+        permissions.set_mode(permissions.mode() | 0o111);
+        fs::set_permissions(path, permissions)?;
+
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn mark_executable(_path: &Path) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn archive_component_artifacts(
+        &mut self,
+        component: &StandaloneComponent,
+        dist_dir: &Path,
+        version: &str,
+    ) -> anyhow::Result<()> {
+        println!("Archiving artifacts for version {version}...");
+
+        let path = (component.archive_prefix)(version);
+
+        self.upload_component_artifacts(dist_dir, &path).await?;
+
+        // A previously failed, partial archive attempt shouldn't leave behind files that don't
+        // belong to this build
+        let archive_prefix = format!("{}/{}", self.config.upload_dir, path);
+        let keep = Self::relative_artifact_keys(dist_dir)?;
+        self.prune_stale_objects(&self.config.upload_bucket, &archive_prefix, &keep)
+            .await
+    }
+
+    /// Copy the artifacts just archived into `dist/`, using a server-side `CopyObject` within
+    /// `UPLOAD_BUCKET` instead of uploading them from disk again.
+    async fn promote_component_artifacts(
+        &mut self,
+        component: &StandaloneComponent,
+        dist_dir: &Path,
+        version: &str,
+    ) -> anyhow::Result<()> {
+        println!("Promoting artifacts to {}...", component.dist_prefix);
+
+        let source_prefix = format!(
+            "{}/{}",
+            self.config.upload_dir,
+            (component.archive_prefix)(version)
+        );
+        let destination_prefix = format!("{}/{}", self.config.upload_dir, component.dist_prefix);
+
+        if self.config.dry_run {
+            println!(
+                "[dry-run] would copy s3://{}/{source_prefix} to s3://{}/{destination_prefix}",
+                self.config.upload_bucket, self.config.upload_bucket
+            );
+        } else {
+            self.s3()
+                .await
+                .copy_prefix(
+                    &self.config.upload_bucket,
+                    &source_prefix,
+                    &self.config.upload_bucket,
+                    &destination_prefix,
+                )
+                .await?;
+        }
+
+        // Binaries for targets dropped since the last release would otherwise linger in dist/
+        // and keep being served by the installer
+        let keep = Self::relative_artifact_keys(dist_dir)?;
+        self.prune_stale_objects(&self.config.upload_bucket, &destination_prefix, &keep)
+            .await
+    }
+
+    /// Collect the paths of every file under `dist_dir`, relative to it, as the set of keys that
+    /// should exist under a prefix mirroring its contents.
+    fn relative_artifact_keys(dist_dir: &Path) -> anyhow::Result<BTreeSet<String>> {
+        Self::component_artifact_paths(dist_dir)?
+            .into_iter()
+            .map(|path| {
+                path.strip_prefix(dist_dir)?
+                    .to_str()
+                    .map(|relative| relative.replace(std::path::MAIN_SEPARATOR, "/"))
+                    .ok_or_else(|| anyhow!("artifact path {} is not valid UTF-8", path.display()))
+            })
+            .collect()
+    }
+
+    /// Delete any object under `prefix` whose key (relative to `prefix`) isn't in `keep`, so the
+    /// prefix ends up mirroring exactly what was just promoted. Behind `--dry-run`, objects are
+    /// only logged, not deleted.
+    async fn prune_stale_objects(
+        &mut self,
+        bucket: &str,
+        prefix: &str,
+        keep: &BTreeSet<String>,
+    ) -> anyhow::Result<()> {
+        let s3 = self.s3().await;
+        let all_keys = s3.list_keys(bucket, prefix).await?;
+        let stale = Self::stale_keys(&all_keys, prefix, keep);
+
+        if stale.is_empty() {
+            return Ok(());
+        }
+
+        if self.config.dry_run {
+            for key in &stale {
+                println!("[dry-run] would remove stale object s3://{bucket}/{key}");
+            }
+
+            return Ok(());
+        }
+
+        for key in &stale {
+            println!("Removing stale object s3://{bucket}/{key}");
+        }
+
+        s3.delete_objects(bucket, &stale).await
+    }
+
+    /// Of `all_keys`, those whose part after `prefix` isn't in `keep`; a key not under `prefix` at
+    /// all counts as stale too. Pulled out of [`Self::prune_stale_objects`] so the filtering
+    /// itself is unit-testable without S3.
+    fn stale_keys(all_keys: &[String], prefix: &str, keep: &BTreeSet<String>) -> Vec<String> {
+        all_keys
+            .iter()
+            .filter(|key| {
+                key.strip_prefix(prefix)
+                    .map(|relative| !keep.contains(relative))
+                    .unwrap_or(true)
+            })
+            .cloned()
+            .collect()
+    }
+
+    async fn upload_component_artifacts(
+        &mut self,
+        dist_dir: &Path,
+        target_path: &str,
+    ) -> anyhow::Result<()> {
+        let destination_prefix = format!("{}/{}", self.config.upload_dir, target_path);
+
+        if self.config.dry_run {
+            println!(
+                "[dry-run] would upload {} to s3://{}/{destination_prefix}",
+                dist_dir.display(),
+                self.config.upload_bucket
+            );
+            return Ok(());
+        }
+
+        self.s3()
+            .await
+            .upload_prefix(dist_dir, &self.config.upload_bucket, &destination_prefix)
+            .await
+    }
+
+    async fn update_component_release(
+        &mut self,
+        component: &StandaloneComponent,
+        version: &str,
+        checksums: &BTreeMap<String, String>,
+    ) -> anyhow::Result<()> {
+        println!("Updating version and manifest...");
+
+        let manifest_path = self.dl_dir().join(component.manifest_name);
+        let manifest = toml::to_string(&Manifest {
+            schema_version: "1",
+            version,
+            checksums,
+        })?;
+
+        fs::write(&manifest_path, &manifest)?;
+
+        let key = format!("{}/{}", self.config.upload_dir, component.manifest_name);
+
+        if self.config.dry_run {
+            println!(
+                "[dry-run] would upload {} to s3://{}/{key}",
+                manifest_path.display(),
+                self.config.upload_bucket
+            );
+            return Ok(());
+        }
+
+        self.s3()
+            .await
+            .put_object(&manifest_path, &self.config.upload_bucket, &key)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_sidecar_path_appends_rather_than_replaces_the_extension() {
+        assert_eq!(
+            Context::checksum_sidecar_path(Path::new("/dist/rustup-init.exe")),
+            Path::new("/dist/rustup-init.exe.sha256"),
+        );
+        assert_eq!(
+            Context::checksum_sidecar_path(Path::new("/dist/rustup-init.tar.gz")),
+            Path::new("/dist/rustup-init.tar.gz.sha256"),
+        );
+    }
+
+    #[test]
+    fn checksum_sidecar_path_does_not_collide_across_archive_formats() {
+        let gz = Context::checksum_sidecar_path(Path::new("/dist/rustup-init.tar.gz"));
+        let xz = Context::checksum_sidecar_path(Path::new("/dist/rustup-init.tar.xz"));
+
+        assert_ne!(gz, xz);
+    }
+
+    #[test]
+    fn find_component_artifact_matches_target_triple_in_the_directory() {
+        let dist_dir = tempdir().unwrap();
+        let target_dir = dist_dir.path().join("x86_64-unknown-linux-gnu");
+        fs::create_dir(&target_dir).unwrap();
+        fs::write(target_dir.join("rustup-init"), b"binary").unwrap();
+
+        let found =
+            Context::find_component_artifact(dist_dir.path(), "x86_64-unknown-linux-gnu")
+                .unwrap();
+
+        assert_eq!(found, target_dir.join("rustup-init"));
+    }
+
+    #[test]
+    fn find_component_artifact_fails_when_no_artifact_matches() {
+        let dist_dir = tempdir().unwrap();
+        fs::create_dir(dist_dir.path().join("aarch64-apple-darwin")).unwrap();
+
+        assert!(
+            Context::find_component_artifact(dist_dir.path(), "x86_64-unknown-linux-gnu")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn version_may_be_promoted_refuses_to_downgrade_or_republish() {
+        let published = Version::parse("1.2.0").unwrap();
+
+        assert!(!Context::version_may_be_promoted(
+            &Version::parse("1.2.0").unwrap(),
+            Some(&published),
+            false
+        ));
+        assert!(!Context::version_may_be_promoted(
+            &Version::parse("1.1.0").unwrap(),
+            Some(&published),
+            false
+        ));
+        assert!(Context::version_may_be_promoted(
+            &Version::parse("1.3.0").unwrap(),
+            Some(&published),
+            false
+        ));
+    }
+
+    #[test]
+    fn version_may_be_promoted_honors_allow_rerelease() {
+        let published = Version::parse("1.2.0").unwrap();
+
+        assert!(Context::version_may_be_promoted(
+            &Version::parse("1.2.0").unwrap(),
+            Some(&published),
+            true
+        ));
+    }
+
+    #[test]
+    fn version_may_be_promoted_allows_any_version_when_nothing_is_published() {
+        assert!(Context::version_may_be_promoted(
+            &Version::parse("0.1.0").unwrap(),
+            None,
+            false
+        ));
+    }
+
+    #[test]
+    fn stale_keys_drops_keys_still_in_keep() {
+        let all_keys = vec![
+            "dist/rustup-init".to_owned(),
+            "dist/rustup-init.sha256".to_owned(),
+            "dist/leftover-from-old-build".to_owned(),
+        ];
+        let keep = BTreeSet::from(["rustup-init".to_owned(), "rustup-init.sha256".to_owned()]);
+
+        assert_eq!(
+            Context::stale_keys(&all_keys, "dist/", &keep),
+            vec!["dist/leftover-from-old-build".to_owned()],
+        );
+    }
+
+    #[test]
+    fn stale_keys_treats_a_key_outside_prefix_as_stale() {
+        let all_keys = vec!["other/rustup-init".to_owned()];
+        let keep = BTreeSet::from(["rustup-init".to_owned()]);
+
+        assert_eq!(
+            Context::stale_keys(&all_keys, "dist/", &keep),
+            vec!["other/rustup-init".to_owned()],
+        );
+    }
+
+    #[test]
+    fn relative_artifact_keys_uses_forward_slashes_relative_to_dist_dir() {
+        let dist_dir = tempdir().unwrap();
+        let target_dir = dist_dir.path().join("x86_64-unknown-linux-gnu");
+        fs::create_dir(&target_dir).unwrap();
+        fs::write(target_dir.join("rustup-init"), b"binary").unwrap();
+
+        let keys = Context::relative_artifact_keys(dist_dir.path()).unwrap();
+
+        assert_eq!(
+            keys,
+            BTreeSet::from(["x86_64-unknown-linux-gnu/rustup-init".to_owned()]),
+        );
+    }
+}